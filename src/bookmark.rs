@@ -0,0 +1,58 @@
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, PartialOrd, Debug)]
+pub struct Bookmark {
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub offset: Option<f64>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Bookmark {
+    pub fn new(name: String, offset: Option<f64>, url: Option<String>, tags: Vec<String>) -> Bookmark {
+        Bookmark {
+            id: Uuid::new_v4(),
+            name,
+            offset,
+            url,
+            tags,
+        }
+    }
+
+    /// A terse, human-readable one-line rendering: name, then offset and/or
+    /// URL if present, then tags.
+    pub fn to_plain(&self) -> String {
+        let mut fields = vec![self.name.clone()];
+        fields.extend(self.offset.map(|offset| offset.to_string()));
+        fields.extend(self.url.clone());
+        if !self.tags.is_empty() {
+            fields.push(self.tags.join(","));
+        }
+        fields.join("\t")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_plain_includes_offset_url_and_tags_when_present() {
+        let bare = Bookmark::new("intro".into(), None, None, Vec::new());
+        assert_eq!(bare.to_plain(), "intro");
+
+        let full = Bookmark::new(
+            "credits".into(),
+            Some(12.5),
+            Some("http://example.com".into()),
+            vec!["movie".into(), "favorite".into()],
+        );
+        assert_eq!(full.to_plain(), "credits\t12.5\thttp://example.com\tmovie,favorite");
+    }
+}
@@ -0,0 +1,61 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// Domain errors raised while looking up, mutating, or parsing bookmarks.
+///
+/// Each variant maps to a distinct process exit code (see `exit_code`) so
+/// scripts driving the CLI can branch on *why* it failed instead of just
+/// matching on a non-zero status.
+#[derive(Debug)]
+pub enum ManagerError {
+    /// No bookmark with the given name exists.
+    BookmarkNotFound(String),
+    /// A bookmark with the given name already exists (raised by `add` when
+    /// the caller asked not to clobber an existing entry, e.g. the CLI's
+    /// `add --no-clobber`).
+    DuplicateBookmark(String),
+    /// The bookmark file's contents could not be parsed as YAML.
+    MalformedBookmarkFile { line: u32, content: String },
+}
+
+impl ManagerError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ManagerError::BookmarkNotFound(_) => 2,
+            ManagerError::DuplicateBookmark(_) => 3,
+            ManagerError::MalformedBookmarkFile { .. } => 4,
+        }
+    }
+}
+
+impl Error for ManagerError {}
+
+impl Display for ManagerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ManagerError::BookmarkNotFound(name) => write!(f, "no bookmark named '{}'", name),
+            ManagerError::DuplicateBookmark(name) => write!(f, "a bookmark named '{}' already exists", name),
+            ManagerError::MalformedBookmarkFile { line, content } => {
+                write!(f, "bookmark file is not valid YAML at line {}: {}", line, content)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_are_distinct_per_variant() {
+        let not_found = ManagerError::BookmarkNotFound("x".into()).exit_code();
+        let duplicate = ManagerError::DuplicateBookmark("x".into()).exit_code();
+        let malformed = ManagerError::MalformedBookmarkFile { line: 1, content: "x".into() }.exit_code();
+
+        assert_ne!(not_found, duplicate);
+        assert_ne!(not_found, malformed);
+        assert_ne!(duplicate, malformed);
+    }
+}
@@ -1,8 +1,3 @@
-use serde_derive::{
-    Serialize,
-    Deserialize,
-};
-
 #[macro_use]
 extern crate clap;
 
@@ -12,26 +7,28 @@ use clap::{
     ArgMatches};
 
 use std::{
-    cmp::Ordering,
     error::Error,
-    fmt::{
-        self,
-        Display,
-        Formatter,
-    },
-    fs::{
-        File,
-        OpenOptions,
-    },
+    fs::File,
     io::{
-        Seek,
-        SeekFrom,
         stdout,
         Write,
     },
     path::Path,
 };
 
+use regex::Regex;
+use serde::Serialize;
+
+mod bookmark;
+mod error;
+mod store;
+#[cfg(test)]
+mod test_support;
+
+use bookmark::Bookmark;
+use error::ManagerError;
+use store::BookmarkStore;
+
 /*
 1. Add/update bookmark(if it already exists)
 
@@ -53,6 +50,12 @@ fn main() -> Result<(), String> {
             .value_name("FILE")
             .help("The file to write the output to")
             .default_value("-"))
+        .arg(Arg::with_name("format")
+            .long("format")
+            .value_name("FORMAT")
+            .help("The format to write query/list output in")
+            .possible_values(&["debug", "plain", "json", "yaml"])
+            .default_value("debug"))
         .subcommand(SubCommand::with_name("add")
             .about("Add a new bookmark")
             .arg(Arg::with_name("name")
@@ -67,8 +70,23 @@ fn main() -> Result<(), String> {
                 .long("offset")
                 .value_name("OFFSET")
                 .help("The time offset to save")
+                .takes_value(true))
+            .arg(Arg::with_name("url")
+                .short("u")
+                .long("url")
+                .value_name("URL")
+                .help("A URL to associate with the bookmark")
+                .takes_value(true))
+            .arg(Arg::with_name("tag")
+                .short("t")
+                .long("tag")
+                .value_name("TAG")
+                .help("A tag to associate with the bookmark (may be repeated)")
                 .takes_value(true)
-                .required(true)))
+                .multiple(true))
+            .arg(Arg::with_name("no-clobber")
+                .long("no-clobber")
+                .help("Fail instead of updating an existing bookmark with the same name")))
         .subcommand(SubCommand::with_name("remove")
             .about("Remove an existing bookmark")
             .arg(Arg::with_name("name")
@@ -87,136 +105,159 @@ fn main() -> Result<(), String> {
                 .help("The name of the bookmark to search for")
                 .takes_value(true)
                 .required(true)))
+        .subcommand(SubCommand::with_name("list")
+            .about("List all bookmarks, optionally filtered by name")
+            .arg(Arg::with_name("pattern")
+                .short("p")
+                .long("pattern")
+                .value_name("PATTERN")
+                .help("A regex pattern to filter bookmark names by")
+                .takes_value(true)))
         .get_matches();
 
     let return_value = control(&matches);
 
     match return_value {
-        Ok(Some(val)) => {
-            let output = value_t!(matches, "output", String).unwrap_or("-".into());
-
-            let mut output_writer: Box<dyn Write> = if output == "-" {
-                Box::new(stdout())
-            } else {
-                Box::new(File::create(output).unwrap())
-            };
-
-            writeln!(&mut output_writer, "{:?}", val).unwrap();
-            Ok(())
+        Ok(CommandOutput::Bookmark(val)) => write_output(&matches, &val),
+        Ok(CommandOutput::Bookmarks(vals)) => write_output(&matches, &vals),
+        Ok(CommandOutput::None) => Ok(()),
+        Err(err) => {
+            if let Some(err) = err.downcast_ref::<ManagerError>() {
+                std::process::exit(err.exit_code());
+            }
+            Err(err.to_string())
         },
-        Ok(None) => Ok(()),
-        Err(err) => Err(err.to_string()),
     }
 }
 
-fn control(matches: &ArgMatches) -> Result<Option<Bookmark>, Box<dyn Error>> {
-    match matches.subcommand_name() {
-        Some("add") => {
-            let filename = value_t!(matches, "file", String)?;
-
-            let matches = matches.subcommand_matches("add").ok_or(ManagerError::new("Failed to find add subcommand"))?;
-            let bookmark_name = value_t!(matches, "name", String)?;
-            let offset = value_t!(matches, "offset", f64)?;
-
-            add_bookmark(filename, bookmark_name, offset)
-        },
-        Some("remove") => {
-            let filename = value_t!(matches, "file", String)?;
-
-            let matches = matches.subcommand_matches("remove").ok_or(ManagerError::new("Failed to find remove subcommand"))?;
-            let bookmark_name = value_t!(matches, "name", String)?;
-
-            remove_bookmark(filename, bookmark_name)
-        },
-        Some("query") => {
-            let filename = value_t!(matches, "file", String)?;
+/// A terse, human-readable rendering for the `plain` output format.
+trait PlainFormat {
+    fn to_plain(&self) -> String;
+}
 
-            let matches = matches.subcommand_matches("query").ok_or(ManagerError::new("Failed to find query subcommand"))?;
-            let bookmark_name = value_t!(matches, "name", String)?;
+impl PlainFormat for Bookmark {
+    fn to_plain(&self) -> String {
+        Bookmark::to_plain(self)
+    }
+}
 
-            query_bookmark(filename, bookmark_name)
-        },
-        _ => Err(Box::new(ManagerError::new("Unable to match subcommand")))
+impl PlainFormat for Vec<Bookmark> {
+    fn to_plain(&self) -> String {
+        self.iter().map(Bookmark::to_plain).collect::<Vec<_>>().join("\n")
     }
 }
 
-fn add_bookmark<P: AsRef<Path>>(file: P, name: String, offset: f64) -> Result<Option<Bookmark>, Box<dyn Error>> {
-    let mut file = OpenOptions::new().read(true).write(true).create(true).open(file)?;
-    let mut bookmarks: Vec<Bookmark> = serde_yaml::from_reader(&file).unwrap_or(Vec::new());
+fn write_output<T: std::fmt::Debug + Serialize + PlainFormat>(matches: &ArgMatches, val: &T) -> Result<(), String> {
+    let output = value_t!(matches, "output", String).unwrap_or("-".into());
+    let format = value_t!(matches, "format", String).unwrap_or("debug".into());
 
-    if bookmarks.iter().find(|x| x.name == name).is_none() {
-        bookmarks.push(Bookmark::new(name, offset));
+    let mut output_writer: Box<dyn Write> = if output == "-" {
+        Box::new(stdout())
     } else {
-        bookmarks.iter_mut().find(|x| x.name == name).unwrap().offset = offset;
+        Box::new(File::create(output).unwrap())
+    };
+
+    match format.as_str() {
+        "plain" => writeln!(&mut output_writer, "{}", val.to_plain()).unwrap(),
+        "json" => serde_json::to_writer_pretty(&mut output_writer, val).unwrap(),
+        "yaml" => serde_yaml::to_writer(&mut output_writer, val).unwrap(),
+        _ => writeln!(&mut output_writer, "{:?}", val).unwrap(),
     }
 
-    bookmarks.sort_unstable_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap_or(Ordering::Equal));
-    bookmarks.dedup_by(|x, y| x.name == y.name);
-
-    file.seek(SeekFrom::Start(0))?;
-    file.set_len(0)?;
+    Ok(())
+}
 
-    serde_yaml::to_writer(&file, &bookmarks)?;
-    Ok(None)
+/// What a subcommand produced, so `main` knows whether to print nothing, a
+/// single bookmark, or a whole list.
+enum CommandOutput {
+    None,
+    Bookmark(Bookmark),
+    Bookmarks(Vec<Bookmark>),
 }
 
-fn remove_bookmark<P: AsRef<Path>>(file: P, name: String) -> Result<Option<Bookmark>, Box<dyn Error>> {
-    let mut file = OpenOptions::new().read(true).write(true).open(file)?;
-    let mut bookmarks: Vec<Bookmark> = serde_yaml::from_reader(&file)?;
+/// Thin dispatcher over `BookmarkStore`: loads the store for the requested
+/// file, applies the subcommand's operation, and saves back to disk after
+/// `add` and a successful `remove`; `query` and `list` never write.
+fn control(matches: &ArgMatches) -> Result<CommandOutput, Box<dyn Error>> {
+    let filename = value_t!(matches, "file", String)?;
+
+    match matches.subcommand_name() {
+        Some("add") => {
+            let matches = matches.subcommand_matches("add").ok_or("failed to find add subcommand")?;
+            let bookmark_name = value_t!(matches, "name", String)?;
+            let offset = matches.value_of("offset").map(str::parse).transpose()?;
+            let url = value_t!(matches, "url", String).ok();
+            let tags = matches.values_of("tag").map_or(Vec::new(), |vals| vals.map(String::from).collect());
+
+            let mut store = BookmarkStore::load(&filename)?;
+            if matches.is_present("no-clobber") {
+                store.add(bookmark_name, offset, url, tags)?;
+            } else if store.add(bookmark_name.clone(), offset, url.clone(), tags.clone()).is_err() {
+                store.update(&bookmark_name, offset, url, tags)?;
+            }
+            store.save()?;
+            Ok(CommandOutput::None)
+        },
+        Some("remove") => {
+            let matches = matches.subcommand_matches("remove").ok_or("failed to find remove subcommand")?;
+            let bookmark_name = value_t!(matches, "name", String)?;
 
-    if let Some(index) = bookmarks.iter().position(|x| x.name == name) {
-        let bookmark: Bookmark = bookmarks.remove(index);
+            let mut store = BookmarkStore::load(&filename)?;
+            let removed = store.remove(&bookmark_name)?;
+            store.save()?;
+            Ok(CommandOutput::Bookmark(removed))
+        },
+        Some("query") => {
+            let matches = matches.subcommand_matches("query").ok_or("failed to find query subcommand")?;
+            let bookmark_name = value_t!(matches, "name", String)?;
 
-        file.seek(SeekFrom::Start(0))?;
-        file.set_len(0)?;
+            let store = BookmarkStore::load(&filename)?;
+            Ok(CommandOutput::Bookmark(store.get(&bookmark_name)?.clone()))
+        },
+        Some("list") => {
+            let matches = matches.subcommand_matches("list").ok_or("failed to find list subcommand")?;
+            let pattern = value_t!(matches, "pattern", String).ok();
 
-        serde_yaml::to_writer(&file, &bookmarks)?;
-        return Ok(Some(bookmark));
+            Ok(CommandOutput::Bookmarks(list_bookmarks(filename, pattern)?))
+        },
+        _ => Err("unable to match subcommand".into())
     }
-    Ok(None)
 }
 
-fn query_bookmark<P: AsRef<Path>>(file: P, name: String) -> Result<Option<Bookmark>, Box<dyn Error>> {
-    let file = OpenOptions::new().read(true).write(true).open(file)?;
-    let mut bookmarks: Vec<Bookmark> = serde_yaml::from_reader(&file)?;
-
-    let bookmark: Option<Bookmark> = bookmarks.drain(..).filter(|val| val.name == name).next();
+/// Lists every bookmark in `file`, optionally filtered to those whose name
+/// matches `pattern` (compiled once and matched against each `Bookmark.name`).
+fn list_bookmarks<P: AsRef<Path>>(file: P, pattern: Option<String>) -> Result<Vec<Bookmark>, Box<dyn Error>> {
+    let store = BookmarkStore::load(file)?;
+    let pattern = pattern.map(|p| Regex::new(&p)).transpose()?;
 
-    Ok(bookmark)
+    Ok(store.iter()
+        .filter(|bookmark| pattern.as_ref().map_or(true, |re| re.is_match(&bookmark.name)))
+        .cloned()
+        .collect())
 }
 
-#[derive(Serialize, Deserialize, PartialEq, PartialOrd, Debug)]
-pub struct Bookmark {
-    name: String,
-    offset: f64,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_path;
+    use std::fs;
 
-impl Bookmark {
-    pub fn new(name: String, offset: f64) -> Bookmark {
-        Bookmark {
-            name,
-            offset,
-        }
-    }
-}
+    #[test]
+    fn list_bookmarks_filters_by_pattern() {
+        let path = temp_path("list_pattern");
 
-#[derive(Debug)]
-struct ManagerError {
-    message: String,
-}
+        let mut store = BookmarkStore::load(&path).unwrap();
+        store.add("intro".into(), Some(1.0), None, Vec::new()).unwrap();
+        store.add("credits".into(), Some(2.0), None, Vec::new()).unwrap();
+        store.save().unwrap();
 
-impl ManagerError {
-    pub fn new<T: Into<String>>(message: T) -> ManagerError {
-        ManagerError {
-            message: message.into(),
-        }
-    }
-}
+        let filtered = list_bookmarks(&path, Some("^in".into())).unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name, "intro");
 
-impl Error for ManagerError {}
+        let all = list_bookmarks(&path, None).unwrap();
+        assert_eq!(all.len(), 2);
 
-impl Display for ManagerError {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+        fs::remove_file(&path).unwrap();
     }
-}
\ No newline at end of file
+}
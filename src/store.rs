@@ -0,0 +1,164 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::bookmark::Bookmark;
+use crate::error::ManagerError;
+
+/// A bookmark list held in memory and mirrored to a YAML file on disk.
+pub struct BookmarkStore {
+    path: PathBuf,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkStore {
+    /// Loads the bookmarks at `path`, or starts from an empty list if no
+    /// file exists there yet. Nothing is written to disk until `save`, so a
+    /// typo'd path stays non-existent until something actually mutates it.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<BookmarkStore, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(Box::new(err)),
+        };
+
+        let bookmarks = parse_bookmarks(&content)?;
+        Ok(BookmarkStore { path, bookmarks })
+    }
+
+    /// Adds a new bookmark, failing with `DuplicateBookmark` if one with the same name already exists.
+    pub fn add(&mut self, name: String, offset: Option<f64>, url: Option<String>, tags: Vec<String>) -> Result<(), ManagerError> {
+        if self.bookmarks.iter().any(|x| x.name == name) {
+            return Err(ManagerError::DuplicateBookmark(name));
+        }
+
+        self.bookmarks.push(Bookmark::new(name, offset, url, tags));
+        self.bookmarks.sort_unstable_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap_or(Ordering::Equal));
+        Ok(())
+    }
+
+    /// Updates an existing bookmark, failing with `BookmarkNotFound` if none matches.
+    /// `offset`/`url` of `None` and an empty `tags` leave the corresponding
+    /// field untouched, so a partial update (e.g. attaching a URL to a
+    /// bookmark that already has an offset) doesn't clobber the rest.
+    pub fn update(&mut self, name: &str, offset: Option<f64>, url: Option<String>, tags: Vec<String>) -> Result<&Bookmark, ManagerError> {
+        let bookmark = self.bookmarks.iter_mut().find(|x| x.name == name)
+            .ok_or_else(|| ManagerError::BookmarkNotFound(name.to_string()))?;
+
+        if offset.is_some() {
+            bookmark.offset = offset;
+        }
+        if url.is_some() {
+            bookmark.url = url;
+        }
+        if !tags.is_empty() {
+            bookmark.tags = tags;
+        }
+
+        Ok(bookmark)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<Bookmark, ManagerError> {
+        let index = self.bookmarks.iter().position(|x| x.name == name)
+            .ok_or_else(|| ManagerError::BookmarkNotFound(name.to_string()))?;
+        Ok(self.bookmarks.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Bookmark, ManagerError> {
+        self.bookmarks.iter().find(|x| x.name == name)
+            .ok_or_else(|| ManagerError::BookmarkNotFound(name.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.bookmarks.iter()
+    }
+
+    /// Writes via temp file + rename + directory fsync, never in place.
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let tmp_path = self.path.with_extension("tmp");
+
+        let tmp_file = OpenOptions::new().write(true).create(true).truncate(true).open(&tmp_path)?;
+        serde_yaml::to_writer(&tmp_file, &self.bookmarks)?;
+        tmp_file.sync_all()?;
+
+        fs::rename(&tmp_path, &self.path)?;
+
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        File::open(dir)?.sync_all()?;
+
+        Ok(())
+    }
+}
+
+fn parse_bookmarks(content: &str) -> Result<Vec<Bookmark>, ManagerError> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    serde_yaml::from_str(content).map_err(|err| ManagerError::MalformedBookmarkFile {
+        line: err.location().map(|loc| loc.line() as u32).unwrap_or(0),
+        content: content.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_path;
+
+    #[test]
+    fn save_then_load_round_trips_bookmarks() {
+        let path = temp_path("roundtrip");
+
+        let mut store = BookmarkStore::load(&path).unwrap();
+        store.add("foo".into(), Some(1.5), None, Vec::new()).unwrap();
+        store.add("bar".into(), None, Some("http://example.com".into()), vec!["a".into()]).unwrap();
+        store.save().unwrap();
+
+        let reloaded = BookmarkStore::load(&path).unwrap();
+        let foo = reloaded.get("foo").unwrap();
+        let bar = reloaded.get("bar").unwrap();
+
+        assert_eq!(foo.offset, Some(1.5));
+        assert_eq!(bar.url.as_deref(), Some("http://example.com"));
+        assert_eq!(bar.tags, vec!["a".to_string()]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn get_missing_bookmark_returns_not_found() {
+        let path = temp_path("not_found");
+        let store = BookmarkStore::load(&path).unwrap();
+
+        match store.get("missing") {
+            Err(ManagerError::BookmarkNotFound(name)) => assert_eq!(name, "missing"),
+            other => panic!("expected BookmarkNotFound, got {:?}", other),
+        }
+
+        assert!(!path.exists(), "load() must not create the file on its own");
+    }
+
+    #[test]
+    fn add_duplicate_name_is_rejected() {
+        let path = temp_path("duplicate");
+        let mut store = BookmarkStore::load(&path).unwrap();
+        store.add("foo".into(), Some(1.0), None, Vec::new()).unwrap();
+
+        match store.add("foo".into(), Some(2.0), None, Vec::new()) {
+            Err(ManagerError::DuplicateBookmark(name)) => assert_eq!(name, "foo"),
+            other => panic!("expected DuplicateBookmark, got {:?}", other),
+        }
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,17 @@
+#![cfg(test)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// A fresh, not-yet-created path under the OS temp dir, unique per call.
+pub fn temp_path(name: &str) -> PathBuf {
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let path = env::temp_dir().join(format!("bookmark_manager_test_{}_{}_{}.yml", name, std::process::id(), n));
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(path.with_extension("tmp"));
+    path
+}